@@ -2,79 +2,440 @@ use std::collections::HashMap;
 use std::iter::Peekable;
 use std::str::CharIndices;
 
+/// A position in the source text, tracked incrementally as the tokenizer advances so no
+/// second pass over the input is required to report it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: u32,
+    pub column: u32,
+    pub offset: usize,
+}
+
+impl Location {
+    fn start() -> Self {
+        Location {
+            line: 1,
+            column: 1,
+            offset: 0,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct Comments<'a> {
+    line: Vec<&'a str>,
+    block: Vec<(&'a str, &'a str)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScriptGroup {
+    Latin,
+    Han,
+    Hiragana,
+    Katakana,
+    Other,
+}
+
+impl ScriptGroup {
+    fn of(c: char) -> Self {
+        match c {
+            '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' => {
+                ScriptGroup::Han
+            }
+            '\u{3040}'..='\u{309F}' => ScriptGroup::Hiragana,
+            '\u{30A0}'..='\u{30FF}' => ScriptGroup::Katakana,
+            c if c.is_alphanumeric() || c == '_' => ScriptGroup::Latin,
+            _ => ScriptGroup::Other,
+        }
+    }
+}
+
+type TokenResult<'a> = Option<(Location, Result<(TokenOrigin, &'a str), Location>)>;
+
 pub struct TokenIterator<'a> {
     input: &'a str,
     state: State,
     iter: Peekable<CharIndices<'a>>,
+    loc: Location,
+    comments: Comments<'a>,
 }
 
 impl<'a> TokenIterator<'a> {
-    fn skip_literal(iter: &mut Peekable<CharIndices>) -> bool {
-        if iter.peek().is_some_and(|x| x.1 == '"') {
-            iter.next();
-            while iter.next().is_some_and(|x| x.1 != '"') {}
-            return true;
+    /// Registers comment delimiters to skip alongside whitespace: `line` openers run to the
+    /// end of the line, `block` `(opener, closer)` pairs nest (a block comment containing
+    /// its own opener only ends once every nested opener has a matching closer).
+    pub fn with_comments(
+        mut self,
+        line: impl IntoIterator<Item = &'a str>,
+        block: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        self.comments = Comments {
+            line: line.into_iter().collect(),
+            block: block.into_iter().collect(),
+        };
+        self
+    }
+
+    fn advance(iter: &mut Peekable<CharIndices>, loc: &mut Location) -> Option<(usize, char)> {
+        let next = iter.next()?;
+        if next.1 == '\n' {
+            loc.line += 1;
+            loc.column = 1;
+        } else {
+            loc.column += 1;
+        }
+        loc.offset = next.0 + next.1.len_utf8();
+        Some(next)
+    }
+
+    fn advance_by(iter: &mut Peekable<CharIndices>, loc: &mut Location, count: usize) {
+        for _ in 0..count {
+            Self::advance(iter, loc);
+        }
+    }
+
+    fn skip_comment(
+        iter: &mut Peekable<CharIndices>,
+        loc: &mut Location,
+        input: &'a str,
+        comments: &Comments<'a>,
+    ) -> Option<bool> {
+        let rest = &input[loc.offset..];
+        if let Some(opener) = comments.line.iter().find(|o| rest.starts_with(**o)) {
+            Self::advance_by(iter, loc, opener.chars().count());
+            while iter.peek().is_some_and(|x| x.1 != '\n') {
+                Self::advance(iter, loc);
+            }
+            return Some(true);
+        }
+        let (opener, closer) = comments
+            .block
+            .iter()
+            .find(|(opener, _)| rest.starts_with(*opener))?;
+        Self::advance_by(iter, loc, opener.chars().count());
+        let mut depth = 1usize;
+        loop {
+            let rest = &input[loc.offset..];
+            if rest.starts_with(*closer) {
+                Self::advance_by(iter, loc, closer.chars().count());
+                depth -= 1;
+                if depth == 0 {
+                    return Some(true);
+                }
+            } else if rest.starts_with(*opener) {
+                Self::advance_by(iter, loc, opener.chars().count());
+                depth += 1;
+            } else if Self::advance(iter, loc).is_none() {
+                return Some(false);
+            }
+        }
+    }
+
+    fn skip_literal(iter: &mut Peekable<CharIndices>, loc: &mut Location) -> Option<bool> {
+        if iter.peek().is_none_or(|x| x.1 != '"') {
+            return None;
+        }
+        Self::advance(iter, loc);
+        loop {
+            match Self::advance(iter, loc) {
+                Some((_, '"')) => return Some(true),
+                Some((_, '\\')) => {
+                    if Self::advance(iter, loc).is_none() {
+                        return Some(false);
+                    }
+                }
+                Some(_) => continue,
+                None => return Some(false),
+            }
+        }
+    }
+
+    fn skip_numeric(
+        iter: &mut Peekable<CharIndices>,
+        loc: &mut Location,
+    ) -> Option<(TokenOrigin, bool)> {
+        if !iter.peek().is_some_and(|x| x.1.is_ascii_digit()) {
+            return None;
+        }
+        let (_, first_digit) = Self::advance(iter, loc)?;
+        let sigil = (first_digit == '0')
+            .then(|| iter.peek().map(|x| x.1))
+            .flatten()
+            .filter(|c| *c == 'x' || *c == 'b');
+        match sigil {
+            Some(sigil) => {
+                Self::advance(iter, loc);
+                let origin = if sigil == 'x' {
+                    TokenOrigin::HexLiteral
+                } else {
+                    TokenOrigin::BinLiteral
+                };
+                let is_digit: fn(char) -> bool = if sigil == 'x' {
+                    |c| c.is_ascii_hexdigit()
+                } else {
+                    |c| c == '0' || c == '1'
+                };
+                let mut has_digit = false;
+                while let Some(&(_, c)) = iter.peek() {
+                    if is_digit(c) {
+                        has_digit = true;
+                    } else if c != '_' {
+                        break;
+                    }
+                    Self::advance(iter, loc);
+                }
+                Some((origin, has_digit))
+            }
+            None => {
+                while iter
+                    .peek()
+                    .is_some_and(|x| x.1.is_ascii_digit() || x.1 == '_')
+                {
+                    Self::advance(iter, loc);
+                }
+                Some((TokenOrigin::DigitGroup, true))
+            }
+        }
+    }
+
+    fn classify_skip_token(
+        iter: &mut Peekable<CharIndices>,
+        loc: &mut Location,
+        state: &State,
+        input: &'a str,
+        comments: &Comments<'a>,
+        word_scan: fn(&mut Peekable<CharIndices>, &mut Location) -> bool,
+    ) -> (TokenOrigin, bool) {
+        if let Some((origin, is_success)) = Self::skip_numeric(iter, loc) {
+            (origin, is_success)
+        } else if word_scan(iter, loc) {
+            (TokenOrigin::Identifier, true)
+        } else if Self::skip_whitespace(iter, loc) {
+            (TokenOrigin::Whitespace, true)
+        } else if let Some(closed) = Self::skip_literal(iter, loc) {
+            (TokenOrigin::StrLiteral, closed)
+        } else if let Some(closed) = Self::skip_comment(iter, loc, input, comments) {
+            (TokenOrigin::Comment, closed)
+        } else {
+            (
+                TokenOrigin::Keyword,
+                Self::skip_with_state(iter, loc, state),
+            )
         }
-        false
     }
 
-    fn skip_with_condition(iter: &mut Peekable<CharIndices>, condition: fn(char) -> bool) -> bool {
+    fn skip_with_condition(
+        iter: &mut Peekable<CharIndices>,
+        loc: &mut Location,
+        condition: fn(char) -> bool,
+    ) -> bool {
         let index = iter.peek().map(|x| x.0);
         while iter.peek().is_some_and(|x| condition(x.1)) {
-            iter.next();
+            Self::advance(iter, loc);
         }
         index != iter.peek().map(|x| x.0)
     }
 
-    fn skip_alphanumeric(iter: &mut Peekable<CharIndices>) -> bool {
-        Self::skip_with_condition(iter, |c| c.is_alphanumeric() || c == '_')
+    fn skip_alphanumeric(iter: &mut Peekable<CharIndices>, loc: &mut Location) -> bool {
+        Self::skip_with_condition(iter, loc, |c| c.is_alphanumeric() || c == '_')
     }
 
-    fn skip_whitespace(iter: &mut Peekable<CharIndices>) -> bool {
-        Self::skip_with_condition(iter, char::is_whitespace)
+    fn skip_word_segment(iter: &mut Peekable<CharIndices>, loc: &mut Location) -> bool {
+        match iter.peek().map(|x| ScriptGroup::of(x.1)) {
+            Some(ScriptGroup::Latin) => {
+                Self::skip_with_condition(iter, loc, |c| ScriptGroup::of(c) == ScriptGroup::Latin)
+            }
+            Some(ScriptGroup::Other) | None => false,
+            Some(_) => {
+                Self::advance(iter, loc);
+                true
+            }
+        }
+    }
+
+    fn skip_whitespace(iter: &mut Peekable<CharIndices>, loc: &mut Location) -> bool {
+        Self::skip_with_condition(iter, loc, char::is_whitespace)
     }
 
-    fn skip_with_state(iter: &mut Peekable<CharIndices>, mut state: &State) -> bool {
+    fn skip_with_state(
+        iter: &mut Peekable<CharIndices>,
+        loc: &mut Location,
+        mut state: &State,
+    ) -> bool {
         while let Some(next_state) = iter.peek().and_then(|x| state.trans.get(&x.1)) {
-            iter.next();
+            Self::advance(iter, loc);
             state = next_state;
         }
         state.is_end_state
     }
 
-    fn skip_token(iter: &mut Peekable<CharIndices>, state: &State) -> bool {
-        Self::skip_alphanumeric(iter)
-            || Self::skip_whitespace(iter)
-            || Self::skip_literal(iter)
-            || Self::skip_with_state(iter, state)
-    }
-
     fn take_token(
         iter: &mut Peekable<CharIndices>,
+        loc: &mut Location,
         state: &State,
         input: &'a str,
-    ) -> Option<Result<&'a str, usize>> {
-        let index_start = iter.peek().map(|x| x.0)?;
-        let is_success = Self::skip_token(iter, state);
+        comments: &Comments<'a>,
+        word_scan: fn(&mut Peekable<CharIndices>, &mut Location) -> bool,
+    ) -> TokenResult<'a> {
+        iter.peek()?;
+        let start_loc = *loc;
+        let (origin, is_success) =
+            Self::classify_skip_token(iter, loc, state, input, comments, word_scan);
         let index_end = iter.peek().map(|x| x.0).unwrap_or(input.len());
         match is_success {
-            true => Some(Ok(&input[index_start..index_end])),
-            false => Some(Err(index_end)),
+            true => Some((start_loc, Ok((origin, &input[start_loc.offset..index_end])))),
+            // An unterminated literal or block comment is reported at its opener, not at EOF
+            // where scanning stopped; every other failure is reported where scanning stopped.
+            false if matches!(origin, TokenOrigin::StrLiteral | TokenOrigin::Comment) => {
+                Some((start_loc, Err(start_loc)))
+            }
+            false => Some((start_loc, Err(*loc))),
         }
     }
 }
 
 impl<'a> Iterator for TokenIterator<'a> {
-    type Item = Result<&'a str, usize>;
+    type Item = (Location, Result<&'a str, Location>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let token = Self::take_token(&mut self.iter, &self.state, self.input)?;
-        if token.is_err() {
-            self.iter = "".char_indices().peekable();
-        } else if token.is_ok_and(|t| t.chars().next().is_some_and(char::is_whitespace)) {
-            return self.next();
+        match Self::take_token(
+            &mut self.iter,
+            &mut self.loc,
+            &self.state,
+            self.input,
+            &self.comments,
+            Self::skip_alphanumeric,
+        )? {
+            (_, Ok((TokenOrigin::Whitespace | TokenOrigin::Comment, _))) => self.next(),
+            (loc, Ok((_, slice))) => Some((loc, Ok(slice))),
+            (loc, Err(err_loc)) => {
+                self.iter = "".char_indices().peekable();
+                Some((loc, Err(err_loc)))
+            }
+        }
+    }
+}
+
+/// Classified view of a token, identifying which branch of [`TokenIterator::classify_skip_token`]
+/// produced it so downstream code can match on variants instead of re-inspecting the slice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind<'a> {
+    Keyword(&'a str),
+    Identifier(&'a str),
+    StrLiteral(&'a str),
+    Whitespace,
+    DigitGroup(&'a str),
+    HexLiteral(&'a str),
+    BinLiteral(&'a str),
+    Comment(&'a str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenOrigin {
+    Keyword,
+    Identifier,
+    StrLiteral,
+    Whitespace,
+    DigitGroup,
+    HexLiteral,
+    BinLiteral,
+    Comment,
+}
+
+impl TokenOrigin {
+    fn classify(self, slice: &str) -> TokenKind<'_> {
+        match self {
+            TokenOrigin::Keyword => TokenKind::Keyword(slice),
+            TokenOrigin::Identifier => TokenKind::Identifier(slice),
+            TokenOrigin::StrLiteral => TokenKind::StrLiteral(slice),
+            TokenOrigin::Whitespace => TokenKind::Whitespace,
+            TokenOrigin::DigitGroup => TokenKind::DigitGroup(slice),
+            TokenOrigin::HexLiteral => TokenKind::HexLiteral(slice),
+            TokenOrigin::BinLiteral => TokenKind::BinLiteral(slice),
+            TokenOrigin::Comment => TokenKind::Comment(slice),
+        }
+    }
+}
+
+/// Companion to [`TokenIterator`] that yields classified [`TokenKind`]s instead of bare slices.
+/// Unlike `TokenIterator`, whitespace and comments are reported as `TokenKind::Whitespace` /
+/// `TokenKind::Comment` rather than skipped,
+/// since callers can now filter it out by matching on the variant.
+pub struct TokenKindIterator<'a> {
+    inner: TokenIterator<'a>,
+}
+
+impl<'a> TokenKindIterator<'a> {
+    /// See [`TokenIterator::with_comments`].
+    pub fn with_comments(
+        mut self,
+        line: impl IntoIterator<Item = &'a str>,
+        block: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        self.inner = self.inner.with_comments(line, block);
+        self
+    }
+}
+
+impl<'a> Iterator for TokenKindIterator<'a> {
+    type Item = (Location, Result<TokenKind<'a>, Location>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match TokenIterator::take_token(
+            &mut self.inner.iter,
+            &mut self.inner.loc,
+            &self.inner.state,
+            self.inner.input,
+            &self.inner.comments,
+            TokenIterator::skip_alphanumeric,
+        )? {
+            (loc, Ok((origin, slice))) => Some((loc, Ok(origin.classify(slice)))),
+            (loc, Err(err_loc)) => {
+                self.inner.iter = "".char_indices().peekable();
+                Some((loc, Err(err_loc)))
+            }
+        }
+    }
+}
+
+/// Companion to [`TokenIterator`] that segments ideographic/kana runs into individual
+/// single-character tokens instead of one alphanumeric blob, while leaving space-delimited
+/// Latin-script behavior unchanged. See [`Tokenizer::tokenize_segmented`].
+pub struct SegmentedTokenIterator<'a> {
+    inner: TokenIterator<'a>,
+}
+
+impl<'a> SegmentedTokenIterator<'a> {
+    /// See [`TokenIterator::with_comments`].
+    pub fn with_comments(
+        mut self,
+        line: impl IntoIterator<Item = &'a str>,
+        block: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        self.inner = self.inner.with_comments(line, block);
+        self
+    }
+}
+
+impl<'a> Iterator for SegmentedTokenIterator<'a> {
+    type Item = (Location, Result<TokenKind<'a>, Location>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match TokenIterator::take_token(
+            &mut self.inner.iter,
+            &mut self.inner.loc,
+            &self.inner.state,
+            self.inner.input,
+            &self.inner.comments,
+            TokenIterator::skip_word_segment,
+        )? {
+            (_, Ok((TokenOrigin::Whitespace | TokenOrigin::Comment, _))) => self.next(),
+            (loc, Ok((origin, slice))) => Some((loc, Ok(origin.classify(slice)))),
+            (loc, Err(err_loc)) => {
+                self.inner.iter = "".char_indices().peekable();
+                Some((loc, Err(err_loc)))
+            }
         }
-        Some(token)
     }
 }
 
@@ -105,6 +466,29 @@ pub trait Tokenizer {
         &'a self,
         keywords: impl IntoIterator<Item = impl AsRef<str>>,
     ) -> TokenIterator<'a>;
+
+    fn tokenize_kinds<'a>(
+        &'a self,
+        keywords: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> TokenKindIterator<'a> {
+        TokenKindIterator {
+            inner: self.tokenize(keywords),
+        }
+    }
+
+    /// Like [`Tokenizer::tokenize`], but splits ideographic/kana runs into individual
+    /// single-character tokens instead of one alphanumeric blob, since those scripts don't
+    /// delimit words with whitespace. Latin-script input is segmented the same as `tokenize`,
+    /// and whitespace/comments are dropped from the stream the same way, rather than reported
+    /// as tokens the way `tokenize_kinds` reports them.
+    fn tokenize_segmented<'a>(
+        &'a self,
+        keywords: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> SegmentedTokenIterator<'a> {
+        SegmentedTokenIterator {
+            inner: self.tokenize(keywords),
+        }
+    }
 }
 
 impl Tokenizer for str {
@@ -118,8 +502,10 @@ impl Tokenizer for str {
         }
         TokenIterator {
             input: self,
-            state: state,
+            state,
             iter: self.char_indices().peekable(),
+            loc: Location::start(),
+            comments: Comments::default(),
         }
     }
 }
@@ -128,6 +514,16 @@ impl Tokenizer for str {
 mod tests {
     use super::*;
 
+    /// Builds the expected `Location` for a byte offset into a single-line ASCII source,
+    /// where column tracks offset 1:1.
+    fn loc(offset: usize) -> Location {
+        Location {
+            line: 1,
+            column: offset as u32 + 1,
+            offset,
+        }
+    }
+
     #[test]
     fn test_state_add_1() {
         let mut state = State::new();
@@ -230,8 +626,14 @@ mod tests {
                     String::from("{"),
                     String::from("}"),
                 ])
-                .collect::<Vec<Result<&str, usize>>>(),
-            vec![Ok("{"), Ok("aaa"), Ok("->"), Ok("bbb"), Ok("}")]
+                .collect::<Vec<(Location, Result<&str, Location>)>>(),
+            vec![
+                (loc(0), Ok("{")),
+                (loc(1), Ok("aaa")),
+                (loc(5), Ok("->")),
+                (loc(7), Ok("bbb")),
+                (loc(11), Ok("}")),
+            ]
         );
     }
 
@@ -245,7 +647,7 @@ mod tests {
                     String::from("{"),
                     String::from("}"),
                 ])
-                .map(Result::unwrap)
+                .map(|(_, token)| token.unwrap())
                 .collect::<Vec<&str>>(),
             vec![
                 "{", "inst_1", "->", "inst_2", "->", "{", "inst_4", "<-", "inst_3", "}", "->",
@@ -258,7 +660,7 @@ mod tests {
     fn test_tokenizer_3() {
         assert_eq!(
             "".tokenize(vec!["->", "<-", "{", "}"])
-                .map(Result::unwrap)
+                .map(|(_, token)| token.unwrap())
                 .collect::<Vec<&str>>(),
             Vec::<&str>::new()
         );
@@ -269,19 +671,22 @@ mod tests {
         assert_eq!(
             "{inst1 -> inst2 -> {inst4 <- inst3} -"
                 .tokenize(["->", "<-", "{", "}"])
-                .collect::<Vec<Result<&str, usize>>>(),
+                .collect::<Vec<(Location, Result<&str, Location>)>>(),
             vec![
-                Ok("{"),
-                Ok("inst1"),
-                Ok("->"),
-                Ok("inst2"),
-                Ok("->"),
-                Ok("{"),
-                Ok("inst4"),
-                Ok("<-"),
-                Ok("inst3"),
-                Ok("}"),
-                Err("{inst1 -> inst2 -> {inst4 <- inst3} -".len())
+                (loc(0), Ok("{")),
+                (loc(1), Ok("inst1")),
+                (loc(7), Ok("->")),
+                (loc(10), Ok("inst2")),
+                (loc(16), Ok("->")),
+                (loc(19), Ok("{")),
+                (loc(20), Ok("inst4")),
+                (loc(26), Ok("<-")),
+                (loc(29), Ok("inst3")),
+                (loc(34), Ok("}")),
+                (
+                    loc(36),
+                    Err(loc("{inst1 -> inst2 -> {inst4 <- inst3} -".len()))
+                ),
             ]
         );
     }
@@ -291,16 +696,16 @@ mod tests {
         assert_eq!(
             "{inst1 -> inst2 -> {inst4 < inst3}"
                 .tokenize(["->", "<-", "{", "}"])
-                .collect::<Vec<Result<&str, usize>>>(),
+                .collect::<Vec<(Location, Result<&str, Location>)>>(),
             vec![
-                Ok("{"),
-                Ok("inst1"),
-                Ok("->"),
-                Ok("inst2"),
-                Ok("->"),
-                Ok("{"),
-                Ok("inst4"),
-                Err("{inst1 -> inst2 -> {inst4 <".len())
+                (loc(0), Ok("{")),
+                (loc(1), Ok("inst1")),
+                (loc(7), Ok("->")),
+                (loc(10), Ok("inst2")),
+                (loc(16), Ok("->")),
+                (loc(19), Ok("{")),
+                (loc(20), Ok("inst4")),
+                (loc(26), Err(loc("{inst1 -> inst2 -> {inst4 <".len()))),
             ]
         );
     }
@@ -310,7 +715,7 @@ mod tests {
         assert_eq!(
             "ab(cd(ef),gh)"
                 .tokenize([":-", "[", "]", "(", ")", ",", "."])
-                .map(Result::unwrap)
+                .map(|(_, token)| token.unwrap())
                 .collect::<Vec<&str>>(),
             vec!["ab", "(", "cd", "(", "ef", ")", ",", "gh", ")"]
         );
@@ -321,8 +726,11 @@ mod tests {
         assert_eq!(
             "a_b*a_c("
                 .tokenize([":-", "[", "]", "(", ")", ",", "."])
-                .collect::<Vec<Result<&str, usize>>>(),
-            vec![Ok("a_b"), Err("a_b".len())]
+                .collect::<Vec<(Location, Result<&str, Location>)>>(),
+            vec![
+                (loc(0), Ok("a_b")),
+                (loc("a_b".len()), Err(loc("a_b".len()))),
+            ]
         );
     }
 
@@ -331,21 +739,9 @@ mod tests {
         assert_eq!(
             "ab(c_d(e_f),g_h)))("
                 .tokenize([":-", "[", "]", "(", ")", ",", "."])
-                .collect::<Vec<Result<&str, usize>>>(),
-            vec![
-                Ok("ab"),
-                Ok("("),
-                Ok("c_d"),
-                Ok("("),
-                Ok("e_f"),
-                Ok(")"),
-                Ok(","),
-                Ok("g_h"),
-                Ok(")"),
-                Ok(")"),
-                Ok(")"),
-                Ok("(")
-            ]
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<&str>>(),
+            vec!["ab", "(", "c_d", "(", "e_f", ")", ",", "g_h", ")", ")", ")", "("]
         );
     }
 
@@ -354,18 +750,9 @@ mod tests {
         assert_eq!(
             "[2]a:-b,c.\n"
                 .tokenize([":-", "[", "]", "(", ")", ",", "."])
-                .collect::<Vec<Result<&str, usize>>>(),
-            vec![
-                Ok("["),
-                Ok("2"),
-                Ok("]"),
-                Ok("a"),
-                Ok(":-"),
-                Ok("b"),
-                Ok(","),
-                Ok("c"),
-                Ok(".")
-            ]
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<&str>>(),
+            vec!["[", "2", "]", "a", ":-", "b", ",", "c", "."]
         );
     }
 
@@ -374,12 +761,62 @@ mod tests {
         assert_eq!(
             "f(a ,b ,X)"
                 .tokenize([":-", "[", "]", "(", ")", ",", "."])
-                .map(Result::unwrap)
+                .map(|(_, token)| token.unwrap())
                 .collect::<Vec<&str>>(),
             vec!["f", "(", "a", ",", "b", ",", "X", ")"]
         );
     }
 
+    #[test]
+    fn test_tokenize_kinds_1() {
+        assert_eq!(
+            "{aaa ->bbb }"
+                .tokenize_kinds(vec![
+                    String::from("->"),
+                    String::from("<-"),
+                    String::from("{"),
+                    String::from("}"),
+                ])
+                .collect::<Vec<(Location, Result<TokenKind, Location>)>>(),
+            vec![
+                (loc(0), Ok(TokenKind::Keyword("{"))),
+                (loc(1), Ok(TokenKind::Identifier("aaa"))),
+                (loc(4), Ok(TokenKind::Whitespace)),
+                (loc(5), Ok(TokenKind::Keyword("->"))),
+                (loc(7), Ok(TokenKind::Identifier("bbb"))),
+                (loc(10), Ok(TokenKind::Whitespace)),
+                (loc(11), Ok(TokenKind::Keyword("}"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_kinds_2() {
+        assert_eq!(
+            r#"(*"3")"#
+                .tokenize_kinds(["(", ")", "*"])
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<TokenKind>>(),
+            vec![
+                TokenKind::Keyword("("),
+                TokenKind::Keyword("*"),
+                TokenKind::StrLiteral(r#""3""#),
+                TokenKind::Keyword(")"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_kinds_3() {
+        assert_eq!(
+            "ab("
+                .tokenize_kinds(["("])
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<TokenKind>>(),
+            vec![TokenKind::Identifier("ab"), TokenKind::Keyword("(")]
+        );
+    }
+
     #[test]
     fn test_tokenizer_11() {
         assert_eq!(
@@ -387,11 +824,264 @@ mod tests {
                 .tokenize([
                     "->", "<-", "(", ")", "{", "=", ",", "}", "[", "|", "]", "*", ".",
                 ])
-                .map(Result::unwrap)
+                .map(|(_, token)| token.unwrap())
                 .collect::<Vec<&str>>(),
             vec![
                 "(", "*", r#""3""#, "->", "int", "->", "*", r#""i""#, ".", "write", ")"
             ]
         );
     }
+
+    #[test]
+    fn test_tokenizer_locations_multiline() {
+        assert_eq!(
+            "a\nbb"
+                .tokenize(Vec::<&str>::new())
+                .collect::<Vec<(Location, Result<&str, Location>)>>(),
+            vec![
+                (
+                    Location {
+                        line: 1,
+                        column: 1,
+                        offset: 0
+                    },
+                    Ok("a")
+                ),
+                (
+                    Location {
+                        line: 2,
+                        column: 1,
+                        offset: 2
+                    },
+                    Ok("bb")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_unterminated_literal() {
+        assert_eq!(
+            "\"abc"
+                .tokenize(Vec::<&str>::new())
+                .collect::<Vec<(Location, Result<&str, Location>)>>(),
+            vec![(loc(0), Err(loc(0)))]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_unterminated_literal_after_identifier() {
+        assert_eq!(
+            "foo \"bar"
+                .tokenize(Vec::<&str>::new())
+                .collect::<Vec<(Location, Result<&str, Location>)>>(),
+            vec![(loc(0), Ok("foo")), (loc(4), Err(loc(4)))]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_literal_escaped_quote() {
+        let input = "\"\\\"\"";
+        assert_eq!(
+            input
+                .tokenize(Vec::<&str>::new())
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<&str>>(),
+            vec![input]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_literal_escaped_quote_mid_string() {
+        let input = "\"a\\\"b\"";
+        assert_eq!(
+            input
+                .tokenize(Vec::<&str>::new())
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<&str>>(),
+            vec![input]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_literal_trailing_backslash_is_unterminated() {
+        assert_eq!(
+            "\"abc\\"
+                .tokenize(Vec::<&str>::new())
+                .collect::<Vec<(Location, Result<&str, Location>)>>(),
+            vec![(loc(0), Err(loc(0)))]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_kinds_numeric() {
+        assert_eq!(
+            "42 1_000 0xFF 0b101"
+                .tokenize_kinds(Vec::<&str>::new())
+                .map(|(_, token)| token.unwrap())
+                .filter(|kind| *kind != TokenKind::Whitespace)
+                .collect::<Vec<TokenKind>>(),
+            vec![
+                TokenKind::DigitGroup("42"),
+                TokenKind::DigitGroup("1_000"),
+                TokenKind::HexLiteral("0xFF"),
+                TokenKind::BinLiteral("0b101"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_kinds_numeric_prefix_then_identifier() {
+        assert_eq!(
+            "2abc"
+                .tokenize_kinds(Vec::<&str>::new())
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<TokenKind>>(),
+            vec![TokenKind::DigitGroup("2"), TokenKind::Identifier("abc")]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_hex_sigil_without_digits_is_err() {
+        assert_eq!(
+            "0x".tokenize(Vec::<&str>::new())
+                .collect::<Vec<(Location, Result<&str, Location>)>>(),
+            vec![(loc(0), Err(loc(2)))]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_bin_sigil_followed_by_non_binary_digit_is_err() {
+        assert_eq!(
+            "0b2"
+                .tokenize(Vec::<&str>::new())
+                .collect::<Vec<(Location, Result<&str, Location>)>>(),
+            vec![(loc(0), Err(loc(2)))]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_line_comment_is_dropped() {
+        assert_eq!(
+            "a // rest of the line\nb"
+                .tokenize(Vec::<&str>::new())
+                .with_comments(["//"], [])
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<&str>>(),
+            vec!["a", "b"]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_block_comment_is_dropped() {
+        assert_eq!(
+            "a /* b\nc */ d"
+                .tokenize(Vec::<&str>::new())
+                .with_comments([], [("/*", "*/")])
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<&str>>(),
+            vec!["a", "d"]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_block_comment_nests() {
+        assert_eq!(
+            "a /* /* b */ c */ d"
+                .tokenize(Vec::<&str>::new())
+                .with_comments([], [("/*", "*/")])
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<&str>>(),
+            vec!["a", "d"]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_unterminated_block_comment_errs_at_opener() {
+        assert_eq!(
+            "a /* b"
+                .tokenize(Vec::<&str>::new())
+                .with_comments([], [("/*", "*/")])
+                .collect::<Vec<(Location, Result<&str, Location>)>>(),
+            vec![(loc(0), Ok("a")), (loc(2), Err(loc(2)))]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_kinds_comment_is_classified() {
+        assert_eq!(
+            "a // hi\nb"
+                .tokenize_kinds(Vec::<&str>::new())
+                .with_comments(["//"], [])
+                .map(|(_, token)| token.unwrap())
+                .filter(|kind| *kind != TokenKind::Whitespace)
+                .collect::<Vec<TokenKind>>(),
+            vec![
+                TokenKind::Identifier("a"),
+                TokenKind::Comment("// hi"),
+                TokenKind::Identifier("b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_segmented_splits_han_run() {
+        assert_eq!(
+            "東京都"
+                .tokenize_segmented(Vec::<&str>::new())
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<TokenKind>>(),
+            vec![
+                TokenKind::Identifier("東"),
+                TokenKind::Identifier("京"),
+                TokenKind::Identifier("都"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_segmented_keeps_latin_word_intact() {
+        assert_eq!(
+            "hello world"
+                .tokenize_segmented(Vec::<&str>::new())
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<TokenKind>>(),
+            vec![
+                TokenKind::Identifier("hello"),
+                TokenKind::Identifier("world"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_segmented_script_transition_is_boundary() {
+        assert_eq!(
+            "abc東京def"
+                .tokenize_segmented(Vec::<&str>::new())
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<TokenKind>>(),
+            vec![
+                TokenKind::Identifier("abc"),
+                TokenKind::Identifier("東"),
+                TokenKind::Identifier("京"),
+                TokenKind::Identifier("def"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_segmented_splits_hiragana_run() {
+        assert_eq!(
+            "ひらがな"
+                .tokenize_segmented(Vec::<&str>::new())
+                .map(|(_, token)| token.unwrap())
+                .collect::<Vec<TokenKind>>(),
+            vec![
+                TokenKind::Identifier("ひ"),
+                TokenKind::Identifier("ら"),
+                TokenKind::Identifier("が"),
+                TokenKind::Identifier("な"),
+            ]
+        );
+    }
 }